@@ -0,0 +1,59 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::error::ErrorKind;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0} not found")]
+    NotFound(&'static str),
+    #[error("{0}")]
+    Conflict(&'static str),
+    #[error(transparent)]
+    Database(sqlx::Error),
+    #[error("{0}")]
+    Validation(String),
+    #[error("unauthorized")]
+    Unauthorized,
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(err: sqlx::Error) -> Self {
+        if let sqlx::Error::Database(db_err) = &err {
+            if db_err.kind() == ErrorKind::UniqueViolation {
+                return Error::Conflict("A record with that value already exists");
+            }
+        }
+        Error::Database(err)
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::Conflict(_) => StatusCode::CONFLICT,
+            Error::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Error::Validation(_) => StatusCode::BAD_REQUEST,
+            Error::Unauthorized => StatusCode::UNAUTHORIZED,
+        };
+
+        let message = match &self {
+            Error::Database(err) => {
+                eprintln!("database error: {}", err);
+                "internal server error".to_string()
+            }
+            _ => self.to_string(),
+        };
+
+        let body = Json(json!({
+            "status": status.as_u16(),
+            "message": message,
+        }));
+
+        (status, body).into_response()
+    }
+}
@@ -1,22 +1,48 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     routing::{delete, get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json};
 use sqlx::{sqlite::SqlitePoolOptions, FromRow, SqlitePool};
-use std::net::SocketAddr;
+use std::{convert::Infallible, net::SocketAddr};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 use tower_http::cors::CorsLayer;
+use utoipa::{IntoParams, ToSchema};
+
+mod auth;
+mod config;
+mod error;
+mod openapi;
+
+use auth::AuthUser;
+use config::Config;
+use error::Error;
+
+const EVENTS_CHANNEL_CAPACITY: usize = 100;
 
 #[derive(Clone)]
 struct AppState {
     pool: SqlitePool,
+    config: Config,
+    events: broadcast::Sender<PersonaEvent>,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize)]
+struct PersonaEvent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
 struct Persona {
     id: i64,
     nombres: String,
@@ -25,7 +51,7 @@ struct Persona {
     direccion: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 struct PersonaRequest {
     nombres: String,
     apellidos: String,
@@ -33,31 +59,77 @@ struct PersonaRequest {
     direccion: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+struct User {
+    id: i64,
+    username: String,
+    password_hash: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RegisterRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Deserialize, IntoParams)]
+struct ListParams {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    search: Option<String>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(PersonaPage = Page<Persona>)]
+struct Page<T> {
+    data: Vec<T>,
+    total: i64,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Usa archivo en el directorio actual (debe existir)
-    let database_url = "sqlite:personas.db";
+    let config = Config::init();
 
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(database_url)
+        .max_connections(config.max_connections)
+        .connect(&config.database_url)
         .await?;
 
-    inicializar_db(&pool).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
 
-    let state = AppState { pool };
+    let addr: SocketAddr = config.bind_addr.parse()?;
+    let (events, _) = broadcast::channel(EVENTS_CHANNEL_CAPACITY);
+    let state = AppState {
+        pool,
+        config,
+        events,
+    };
 
     let app = Router::new()
+        .route("/health", get(health))
+        .route("/register", post(register))
+        .route("/login", post(login))
         .route("/personas", post(crear_persona))
         .route("/personas", get(listar_personas))
+        .route("/personas/events", get(persona_events))
         .route("/personas/:id", get(obtener_persona))
         .route("/personas/:id", put(actualizar_persona))
         .route("/personas/:id", delete(eliminar_persona))
         .with_state(state)
+        .merge(openapi::swagger_ui())
         .layer(CorsLayer::permissive());
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-
     println!("Servidor en http://{}", addr);
 
     axum::serve(
@@ -69,29 +141,113 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn inicializar_db(pool: &SqlitePool) -> anyhow::Result<()> {
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS persona (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            nombres TEXT NOT NULL,
-            apellidos TEXT NOT NULL,
-            dni TEXT NOT NULL UNIQUE,
-            direccion TEXT NOT NULL
-        );
-        "#,
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service and database are reachable"),
+        (status = 503, description = "Database is unreachable"),
+    ),
+    tag = "health"
+)]
+async fn health(State(state): State<AppState>) -> impl IntoResponse {
+    match sqlx::query("SELECT 1").execute(&state.pool).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "status": "ok", "database": "reachable" })),
+        ),
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "degraded", "database": "unreachable" })),
+        ),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/register",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "User registered"),
+        (status = 400, description = "Validation error"),
+        (status = 409, description = "Username already exists"),
+    ),
+    tag = "auth"
+)]
+async fn register(
+    State(state): State<AppState>,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if payload.username.trim().is_empty() || payload.password.is_empty() {
+        return Err(Error::Validation(
+            "username and password are required".into(),
+        ));
+    }
+
+    let password_hash = auth::hash_password(&payload.password)
+        .map_err(|e| Error::Validation(e.to_string()))?;
+
+    let res = sqlx::query("INSERT INTO users (username, password_hash) VALUES (?, ?)")
+        .bind(&payload.username)
+        .bind(&password_hash)
+        .execute(&state.pool)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "id": res.last_insert_rowid() })),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login succeeded, returns a bearer token"),
+        (status = 401, description = "Invalid username or password"),
+    ),
+    tag = "auth"
+)]
+async fn login(
+    State(state): State<AppState>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = sqlx::query_as::<_, User>(
+        "SELECT id, username, password_hash FROM users WHERE username = ?",
     )
-    .execute(pool)
-    .await?;
+    .bind(&payload.username)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(Error::Unauthorized)?;
 
-    Ok(())
+    if !auth::verify_password(&payload.password, &user.password_hash) {
+        return Err(Error::Unauthorized);
+    }
+
+    let token = auth::create_jwt(user.id, state.config.jwt_secret.as_bytes(), state.config.jwt_maxage)
+        .map_err(|e| Error::Validation(e.to_string()))?;
+
+    Ok(Json(json!({ "token": token })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/personas",
+    request_body = PersonaRequest,
+    responses(
+        (status = 201, description = "Persona created"),
+        (status = 409, description = "DNI already exists"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "personas"
+)]
 async fn crear_persona(
     State(state): State<AppState>,
+    AuthUser(_user_id): AuthUser,
     Json(payload): Json<PersonaRequest>,
-) -> impl IntoResponse {
-    let result = sqlx::query(
+) -> Result<impl IntoResponse, Error> {
+    let res = sqlx::query(
         "INSERT INTO persona (nombres, apellidos, dni, direccion) VALUES (?, ?, ?, ?)",
     )
     .bind(&payload.nombres)
@@ -99,74 +255,125 @@ async fn crear_persona(
     .bind(&payload.dni)
     .bind(&payload.direccion)
     .execute(&state.pool)
-    .await;
+    .await?;
 
-    match result {
-        Ok(res) => (
-            StatusCode::CREATED,
-            Json(json!({ "id": res.last_insert_rowid() })),
-        ),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        ),
-    }
+    let id = res.last_insert_rowid();
+    let _ = state.events.send(PersonaEvent {
+        kind: "created",
+        id,
+    });
+
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
 }
 
+#[utoipa::path(
+    get,
+    path = "/personas",
+    params(ListParams),
+    responses(
+        (status = 200, description = "Page of personas", body = PersonaPage),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "personas"
+)]
 async fn listar_personas(
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let result = sqlx::query_as::<_, Persona>(
-        "SELECT id, nombres, apellidos, dni, direccion FROM persona",
+    AuthUser(_user_id): AuthUser,
+    Query(params): Query<ListParams>,
+) -> Result<impl IntoResponse, Error> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let sort_column = match params.sort.as_deref() {
+        Some("apellidos") => "apellidos",
+        Some("dni") => "dni",
+        _ => "id",
+    };
+    let order = match params.order.as_deref() {
+        Some("desc") => "DESC",
+        _ => "ASC",
+    };
+    let search = format!("%{}%", params.search.unwrap_or_default());
+
+    let query = format!(
+        "SELECT id, nombres, apellidos, dni, direccion FROM persona \
+         WHERE nombres LIKE ? OR apellidos LIKE ? OR dni LIKE ? \
+         ORDER BY {sort_column} {order} LIMIT ? OFFSET ?"
+    );
+    let personas = sqlx::query_as::<_, Persona>(&query)
+        .bind(&search)
+        .bind(&search)
+        .bind(&search)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&state.pool)
+        .await?;
+
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM persona WHERE nombres LIKE ? OR apellidos LIKE ? OR dni LIKE ?",
     )
-    .fetch_all(&state.pool)
-    .await;
+    .bind(&search)
+    .bind(&search)
+    .bind(&search)
+    .fetch_one(&state.pool)
+    .await?;
 
-    match result {
-        Ok(personas) => (
-            StatusCode::OK,
-            Json(serde_json::to_value(personas).unwrap()),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        ),
-    }
+    Ok(Json(Page {
+        data: personas,
+        total,
+    }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/personas/{id}",
+    params(("id" = i64, Path, description = "Persona id")),
+    responses(
+        (status = 200, description = "Persona found", body = Persona),
+        (status = 404, description = "Persona not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "personas"
+)]
 async fn obtener_persona(
     Path(id): Path<i64>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let result = sqlx::query_as::<_, Persona>(
+    AuthUser(_user_id): AuthUser,
+) -> Result<impl IntoResponse, Error> {
+    let persona = sqlx::query_as::<_, Persona>(
         "SELECT id, nombres, apellidos, dni, direccion FROM persona WHERE id = ?",
     )
     .bind(id)
     .fetch_optional(&state.pool)
-    .await;
+    .await?
+    .ok_or(Error::NotFound("Persona"))?;
 
-    match result {
-        Ok(Some(persona)) => (
-            StatusCode::OK,
-            Json(serde_json::to_value(persona).unwrap()),
-        ),
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Persona no encontrada" })),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        ),
-    }
+    Ok(Json(persona))
 }
 
+#[utoipa::path(
+    put,
+    path = "/personas/{id}",
+    params(("id" = i64, Path, description = "Persona id")),
+    request_body = PersonaRequest,
+    responses(
+        (status = 200, description = "Persona updated"),
+        (status = 404, description = "Persona not found"),
+        (status = 409, description = "DNI already exists"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "personas"
+)]
 async fn actualizar_persona(
     Path(id): Path<i64>,
     State(state): State<AppState>,
+    AuthUser(_user_id): AuthUser,
     Json(payload): Json<PersonaRequest>,
-) -> impl IntoResponse {
-    let result = sqlx::query(
+) -> Result<impl IntoResponse, Error> {
+    let res = sqlx::query(
         "UPDATE persona SET nombres = ?, apellidos = ?, dni = ?, direccion = ? WHERE id = ?",
     )
     .bind(&payload.nombres)
@@ -175,45 +382,60 @@ async fn actualizar_persona(
     .bind(&payload.direccion)
     .bind(id)
     .execute(&state.pool)
-    .await;
+    .await?;
 
-    match result {
-        Ok(res) if res.rows_affected() > 0 => (
-            StatusCode::OK,
-            Json(json!({ "message": "Persona actualizada" })),
-        ),
-        Ok(_) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Persona no encontrada" })),
-        ),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        ),
+    if res.rows_affected() == 0 {
+        return Err(Error::NotFound("Persona"));
     }
+
+    let _ = state.events.send(PersonaEvent {
+        kind: "updated",
+        id,
+    });
+
+    Ok(Json(json!({ "message": "Persona actualizada" })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/personas/{id}",
+    params(("id" = i64, Path, description = "Persona id")),
+    responses(
+        (status = 200, description = "Persona deleted"),
+        (status = 404, description = "Persona not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "personas"
+)]
 async fn eliminar_persona(
     Path(id): Path<i64>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let result = sqlx::query("DELETE FROM persona WHERE id = ?")
+    AuthUser(_user_id): AuthUser,
+) -> Result<impl IntoResponse, Error> {
+    let res = sqlx::query("DELETE FROM persona WHERE id = ?")
         .bind(id)
         .execute(&state.pool)
-        .await;
+        .await?;
 
-    match result {
-        Ok(res) if res.rows_affected() > 0 => (
-            StatusCode::OK,
-            Json(json!({ "message": "Persona eliminada" })),
-        ),
-        Ok(_) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Persona no encontrada" })),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        ),
+    if res.rows_affected() == 0 {
+        return Err(Error::NotFound("Persona"));
     }
+
+    let _ = state.events.send(PersonaEvent {
+        kind: "deleted",
+        id,
+    });
+
+    Ok(Json(json!({ "message": "Persona eliminada" })))
+}
+
+async fn persona_events(
+    State(state): State<AppState>,
+    AuthUser(_user_id): AuthUser,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(state.events.subscribe())
+        .filter_map(|event| event.ok().and_then(|event| Event::default().json_data(event).ok()))
+        .map(Ok);
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
@@ -0,0 +1,47 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{LoginRequest, Persona, PersonaPage, PersonaRequest, RegisterRequest};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::health,
+        crate::register,
+        crate::login,
+        crate::crear_persona,
+        crate::listar_personas,
+        crate::obtener_persona,
+        crate::actualizar_persona,
+        crate::eliminar_persona,
+    ),
+    components(schemas(Persona, PersonaRequest, RegisterRequest, LoginRequest, PersonaPage)),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "health", description = "Health and readiness checks"),
+        (name = "auth", description = "Registration and authentication"),
+        (name = "personas", description = "Persona CRUD endpoints"),
+    )
+)]
+struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// Mounts Swagger UI at `/swagger-ui` and serves the OpenAPI document at
+/// `/api-docs/openapi.json`.
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi())
+}
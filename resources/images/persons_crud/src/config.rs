@@ -0,0 +1,77 @@
+use serde::Deserialize;
+use std::env;
+
+/// Runtime configuration, loaded from `config.toml` (if present) and then overlaid with
+/// environment variables so the container is configurable at `docker run` time.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_database_url")]
+    pub database_url: String,
+    #[serde(default = "default_bind_addr")]
+    pub bind_addr: String,
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default = "default_jwt_secret")]
+    pub jwt_secret: String,
+    #[serde(default = "default_jwt_maxage")]
+    pub jwt_maxage: i64,
+}
+
+impl Config {
+    pub fn init() -> Self {
+        let mut config = std::fs::read_to_string("config.toml")
+            .ok()
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default();
+
+        if let Ok(v) = env::var("DATABASE_URL") {
+            config.database_url = v;
+        }
+        if let Ok(v) = env::var("BIND_ADDR") {
+            config.bind_addr = v;
+        }
+        if let Some(v) = env::var("MAX_CONNECTIONS").ok().and_then(|v| v.parse().ok()) {
+            config.max_connections = v;
+        }
+        if let Ok(v) = env::var("JWT_SECRET") {
+            config.jwt_secret = v;
+        }
+        if let Some(v) = env::var("JWT_MAXAGE").ok().and_then(|v| v.parse().ok()) {
+            config.jwt_maxage = v;
+        }
+
+        config
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: default_database_url(),
+            bind_addr: default_bind_addr(),
+            max_connections: default_max_connections(),
+            jwt_secret: default_jwt_secret(),
+            jwt_maxage: default_jwt_maxage(),
+        }
+    }
+}
+
+fn default_database_url() -> String {
+    "sqlite:persons.db".to_string()
+}
+
+fn default_bind_addr() -> String {
+    "0.0.0.0:3000".to_string()
+}
+
+fn default_max_connections() -> u32 {
+    5
+}
+
+fn default_jwt_secret() -> String {
+    "change-me-in-config".to_string()
+}
+
+fn default_jwt_maxage() -> i64 {
+    60 * 60
+}
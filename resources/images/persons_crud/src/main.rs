@@ -1,22 +1,48 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    response::IntoResponse,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
     routing::{delete, get, post, put},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json};
 use sqlx::{sqlite::SqlitePoolOptions, FromRow, SqlitePool};
-use std::net::SocketAddr;
+use std::{convert::Infallible, net::SocketAddr};
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 use tower_http::cors::CorsLayer;
+use utoipa::{IntoParams, ToSchema};
+
+mod auth;
+mod config;
+mod error;
+mod openapi;
+
+use auth::AuthUser;
+use config::Config;
+use error::Error;
+
+const EVENTS_CHANNEL_CAPACITY: usize = 100;
 
 #[derive(Clone)]
 struct AppState {
     pool: SqlitePool,
+    config: Config,
+    events: broadcast::Sender<PersonEvent>,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize)]
+struct PersonEvent {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct Person {
     id: i64,
@@ -26,7 +52,7 @@ struct Person {
     address: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 struct PersonRequest {
     first_name: String,
@@ -35,31 +61,77 @@ struct PersonRequest {
     address: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+struct User {
+    id: i64,
+    username: String,
+    password_hash: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct RegisterRequest {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 20;
+const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Debug, Deserialize, IntoParams)]
+struct ListParams {
+    limit: Option<i64>,
+    offset: Option<i64>,
+    search: Option<String>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(PersonPage = Page<Person>)]
+struct Page<T> {
+    data: Vec<T>,
+    total: i64,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Usa archivo en el directorio actual (debe existir)
-    let database_url = "sqlite:persons.db";
+    let config = Config::init();
 
     let pool = SqlitePoolOptions::new()
-        .max_connections(5)
-        .connect(database_url)
+        .max_connections(config.max_connections)
+        .connect(&config.database_url)
         .await?;
 
-    inicializar_db(&pool).await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
 
-    let state = AppState { pool };
+    let addr: SocketAddr = config.bind_addr.parse()?;
+    let (events, _) = broadcast::channel(EVENTS_CHANNEL_CAPACITY);
+    let state = AppState {
+        pool,
+        config,
+        events,
+    };
 
     let app = Router::new()
+        .route("/health", get(health))
+        .route("/register", post(register))
+        .route("/login", post(login))
         .route("/persons", post(crear_person))
         .route("/persons", get(listar_persons))
+        .route("/persons/events", get(person_events))
         .route("/persons/:id", get(obtener_person))
         .route("/persons/:id", put(actualizar_person))
         .route("/persons/:id", delete(eliminar_person))
         .with_state(state)
+        .merge(openapi::swagger_ui())
         .layer(CorsLayer::permissive());
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
-
     println!("Servidor en http://{}", addr);
 
     axum::serve(
@@ -71,29 +143,113 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn inicializar_db(pool: &SqlitePool) -> anyhow::Result<()> {
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS person (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            first_name TEXT NOT NULL,
-            last_name TEXT NOT NULL,
-            dni TEXT NOT NULL UNIQUE,
-            address TEXT NOT NULL
-        );
-        "#,
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses(
+        (status = 200, description = "Service and database are reachable"),
+        (status = 503, description = "Database is unreachable"),
+    ),
+    tag = "health"
+)]
+async fn health(State(state): State<AppState>) -> impl IntoResponse {
+    match sqlx::query("SELECT 1").execute(&state.pool).await {
+        Ok(_) => (
+            StatusCode::OK,
+            Json(json!({ "status": "ok", "database": "reachable" })),
+        ),
+        Err(_) => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({ "status": "degraded", "database": "unreachable" })),
+        ),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/register",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "User registered"),
+        (status = 400, description = "Validation error"),
+        (status = 409, description = "Username already exists"),
+    ),
+    tag = "auth"
+)]
+async fn register(
+    State(state): State<AppState>,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<impl IntoResponse, Error> {
+    if payload.username.trim().is_empty() || payload.password.is_empty() {
+        return Err(Error::Validation(
+            "username and password are required".into(),
+        ));
+    }
+
+    let password_hash = auth::hash_password(&payload.password)
+        .map_err(|e| Error::Validation(e.to_string()))?;
+
+    let res = sqlx::query("INSERT INTO users (username, password_hash) VALUES (?, ?)")
+        .bind(&payload.username)
+        .bind(&password_hash)
+        .execute(&state.pool)
+        .await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "id": res.last_insert_rowid() })),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login succeeded, returns a bearer token"),
+        (status = 401, description = "Invalid username or password"),
+    ),
+    tag = "auth"
+)]
+async fn login(
+    State(state): State<AppState>,
+    Json(payload): Json<LoginRequest>,
+) -> Result<impl IntoResponse, Error> {
+    let user = sqlx::query_as::<_, User>(
+        "SELECT id, username, password_hash FROM users WHERE username = ?",
     )
-    .execute(pool)
-    .await?;
+    .bind(&payload.username)
+    .fetch_optional(&state.pool)
+    .await?
+    .ok_or(Error::Unauthorized)?;
 
-    Ok(())
+    if !auth::verify_password(&payload.password, &user.password_hash) {
+        return Err(Error::Unauthorized);
+    }
+
+    let token = auth::create_jwt(user.id, state.config.jwt_secret.as_bytes(), state.config.jwt_maxage)
+        .map_err(|e| Error::Validation(e.to_string()))?;
+
+    Ok(Json(json!({ "token": token })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/persons",
+    request_body = PersonRequest,
+    responses(
+        (status = 201, description = "Person created"),
+        (status = 409, description = "DNI already exists"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "persons"
+)]
 async fn crear_person(
     State(state): State<AppState>,
+    AuthUser(_user_id): AuthUser,
     Json(payload): Json<PersonRequest>,
-) -> impl IntoResponse {
-    let result = sqlx::query(
+) -> Result<impl IntoResponse, Error> {
+    let res = sqlx::query(
         "INSERT INTO person (first_name, last_name, dni, address) VALUES (?, ?, ?, ?)",
     )
     .bind(&payload.first_name)
@@ -101,74 +257,125 @@ async fn crear_person(
     .bind(&payload.dni)
     .bind(&payload.address)
     .execute(&state.pool)
-    .await;
+    .await?;
 
-    match result {
-        Ok(res) => (
-            StatusCode::CREATED,
-            Json(json!({ "id": res.last_insert_rowid() })),
-        ),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        ),
-    }
+    let id = res.last_insert_rowid();
+    let _ = state.events.send(PersonEvent {
+        kind: "created",
+        id,
+    });
+
+    Ok((StatusCode::CREATED, Json(json!({ "id": id }))))
 }
 
+#[utoipa::path(
+    get,
+    path = "/persons",
+    params(ListParams),
+    responses(
+        (status = 200, description = "Page of persons", body = PersonPage),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "persons"
+)]
 async fn listar_persons(
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let result = sqlx::query_as::<_, Person>(
-        "SELECT id, first_name, last_name, dni, address FROM person",
+    AuthUser(_user_id): AuthUser,
+    Query(params): Query<ListParams>,
+) -> Result<impl IntoResponse, Error> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let offset = params.offset.unwrap_or(0).max(0);
+
+    let sort_column = match params.sort.as_deref() {
+        Some("last_name") => "last_name",
+        Some("dni") => "dni",
+        _ => "id",
+    };
+    let order = match params.order.as_deref() {
+        Some("desc") => "DESC",
+        _ => "ASC",
+    };
+    let search = format!("%{}%", params.search.unwrap_or_default());
+
+    let query = format!(
+        "SELECT id, first_name, last_name, dni, address FROM person \
+         WHERE first_name LIKE ? OR last_name LIKE ? OR dni LIKE ? \
+         ORDER BY {sort_column} {order} LIMIT ? OFFSET ?"
+    );
+    let persons = sqlx::query_as::<_, Person>(&query)
+        .bind(&search)
+        .bind(&search)
+        .bind(&search)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&state.pool)
+        .await?;
+
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM person WHERE first_name LIKE ? OR last_name LIKE ? OR dni LIKE ?",
     )
-    .fetch_all(&state.pool)
-    .await;
+    .bind(&search)
+    .bind(&search)
+    .bind(&search)
+    .fetch_one(&state.pool)
+    .await?;
 
-    match result {
-        Ok(persons) => (
-            StatusCode::OK,
-            Json(serde_json::to_value(persons).unwrap()),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        ),
-    }
+    Ok(Json(Page {
+        data: persons,
+        total,
+    }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/persons/{id}",
+    params(("id" = i64, Path, description = "Person id")),
+    responses(
+        (status = 200, description = "Person found", body = Person),
+        (status = 404, description = "Person not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "persons"
+)]
 async fn obtener_person(
     Path(id): Path<i64>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let result = sqlx::query_as::<_, Person>(
+    AuthUser(_user_id): AuthUser,
+) -> Result<impl IntoResponse, Error> {
+    let person = sqlx::query_as::<_, Person>(
         "SELECT id, first_name, last_name, dni, address FROM person WHERE id = ?",
     )
     .bind(id)
     .fetch_optional(&state.pool)
-    .await;
+    .await?
+    .ok_or(Error::NotFound("Person"))?;
 
-    match result {
-        Ok(Some(person)) => (
-            StatusCode::OK,
-            Json(serde_json::to_value(person).unwrap()),
-        ),
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Person not found" })),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        ),
-    }
+    Ok(Json(person))
 }
 
+#[utoipa::path(
+    put,
+    path = "/persons/{id}",
+    params(("id" = i64, Path, description = "Person id")),
+    request_body = PersonRequest,
+    responses(
+        (status = 200, description = "Person updated"),
+        (status = 404, description = "Person not found"),
+        (status = 409, description = "DNI already exists"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "persons"
+)]
 async fn actualizar_person(
     Path(id): Path<i64>,
     State(state): State<AppState>,
+    AuthUser(_user_id): AuthUser,
     Json(payload): Json<PersonRequest>,
-) -> impl IntoResponse {
-    let result = sqlx::query(
+) -> Result<impl IntoResponse, Error> {
+    let res = sqlx::query(
         "UPDATE person SET first_name = ?, last_name = ?, dni = ?, address = ? WHERE id = ?",
     )
     .bind(&payload.first_name)
@@ -177,45 +384,60 @@ async fn actualizar_person(
     .bind(&payload.address)
     .bind(id)
     .execute(&state.pool)
-    .await;
+    .await?;
 
-    match result {
-        Ok(res) if res.rows_affected() > 0 => (
-            StatusCode::OK,
-            Json(json!({ "message": "Person updated" })),
-        ),
-        Ok(_) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Person not found" })),
-        ),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "error": e.to_string() })),
-        ),
+    if res.rows_affected() == 0 {
+        return Err(Error::NotFound("Person"));
     }
+
+    let _ = state.events.send(PersonEvent {
+        kind: "updated",
+        id,
+    });
+
+    Ok(Json(json!({ "message": "Person updated" })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/persons/{id}",
+    params(("id" = i64, Path, description = "Person id")),
+    responses(
+        (status = 200, description = "Person deleted"),
+        (status = 404, description = "Person not found"),
+    ),
+    security(("bearer_auth" = [])),
+    tag = "persons"
+)]
 async fn eliminar_person(
     Path(id): Path<i64>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    let result = sqlx::query("DELETE FROM person WHERE id = ?")
+    AuthUser(_user_id): AuthUser,
+) -> Result<impl IntoResponse, Error> {
+    let res = sqlx::query("DELETE FROM person WHERE id = ?")
         .bind(id)
         .execute(&state.pool)
-        .await;
+        .await?;
 
-    match result {
-        Ok(res) if res.rows_affected() > 0 => (
-            StatusCode::OK,
-            Json(json!({ "message": "Person deleted" })),
-        ),
-        Ok(_) => (
-            StatusCode::NOT_FOUND,
-            Json(json!({ "error": "Person not found" })),
-        ),
-        Err(e) => (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({ "error": e.to_string() })),
-        ),
+    if res.rows_affected() == 0 {
+        return Err(Error::NotFound("Person"));
     }
+
+    let _ = state.events.send(PersonEvent {
+        kind: "deleted",
+        id,
+    });
+
+    Ok(Json(json!({ "message": "Person deleted" })))
+}
+
+async fn person_events(
+    State(state): State<AppState>,
+    AuthUser(_user_id): AuthUser,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(state.events.subscribe())
+        .filter_map(|event| event.ok().and_then(|event| Event::default().json_data(event).ok()))
+        .map(Ok);
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
 }
@@ -0,0 +1,49 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    LoginRequest, Person, PersonPage, PersonRequest, RegisterRequest,
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        crate::health,
+        crate::register,
+        crate::login,
+        crate::crear_person,
+        crate::listar_persons,
+        crate::obtener_person,
+        crate::actualizar_person,
+        crate::eliminar_person,
+    ),
+    components(schemas(Person, PersonRequest, RegisterRequest, LoginRequest, PersonPage)),
+    modifiers(&SecurityAddon),
+    tags(
+        (name = "health", description = "Health and readiness checks"),
+        (name = "auth", description = "Registration and authentication"),
+        (name = "persons", description = "Person CRUD endpoints"),
+    )
+)]
+struct ApiDoc;
+
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// Mounts Swagger UI at `/swagger-ui` and serves the OpenAPI document at
+/// `/api-docs/openapi.json`.
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi())
+}